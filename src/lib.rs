@@ -97,5 +97,7 @@
 //! ```
 
 mod proof_of_work;
+mod retarget;
 
-pub use proof_of_work::Pow;
+pub use proof_of_work::{Pow, PowHasher, ProveControl, ProveOutcome, Sha256Hasher};
+pub use retarget::Retarget;
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+/// The maximum representable score, i.e. `u128::max_value()` as a float. Used as
+/// the `m` in the attempts/difficulty conversions described in the crate docs.
+const M: f64 = u128::max_value() as f64;
+
+/// Tracks recent solve times and recomputes difficulty to hit a target mean
+/// solve time, a la bitcoin.
+///
+/// Difficulty is stored in the same `u128` threshold form used by [`Pow`], but
+/// retargeting happens in the "expected attempts" domain where scaling by a
+/// time ratio is linear. The two are related by `a = m / (m - d)` and
+/// `d = m - m / a` with `m = u128::max_value()`.
+///
+/// [`Pow`]: crate::Pow
+#[derive(Clone, Debug)]
+pub struct Retarget {
+    expected_secs: f64,
+    max_adjustment: f64,
+    window: usize,
+    difficulty: u128,
+    samples: VecDeque<f64>,
+}
+
+impl Retarget {
+    /// Create a retargeter aiming for `expected_secs` per proof, starting from
+    /// `difficulty`, averaging over the last `window` solves and never moving
+    /// difficulty by more than `max_adjustment`x in a single step.
+    ///
+    /// The adjustment clamp resists timestamp manipulation; bitcoin uses a 4x
+    /// bound for the same reason.
+    pub fn new(expected_secs: f64, difficulty: u128, window: usize, max_adjustment: f64) -> Self {
+        Retarget {
+            expected_secs,
+            max_adjustment: max_adjustment.max(1.0),
+            window: window.max(1),
+            difficulty,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Feed the measured duration of a single solved proof, evicting the oldest
+    /// sample once the window is full.
+    pub fn record_solve(&mut self, secs: f64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(secs);
+    }
+
+    /// Feed several solve samples at once.
+    pub fn record_solves<I: IntoIterator<Item = f64>>(&mut self, secs: I) {
+        for sample in secs {
+            self.record_solve(sample);
+        }
+    }
+
+    /// Adopt `difficulty` as the current setting, typically the value returned
+    /// by [`next_difficulty`] once it has been applied to a live chain.
+    ///
+    /// [`next_difficulty`]: Retarget::next_difficulty
+    pub fn set_difficulty(&mut self, difficulty: u128) {
+        self.difficulty = difficulty;
+    }
+
+    /// The difficulty currently in force.
+    pub fn difficulty(&self) -> u128 {
+        self.difficulty
+    }
+
+    /// Recompute difficulty from the observed mean solve time over the window.
+    ///
+    /// With no samples yet there is nothing to retarget on, so the current
+    /// difficulty is returned unchanged.
+    pub fn next_difficulty(&self) -> u128 {
+        if self.samples.is_empty() {
+            return self.difficulty;
+        }
+        let actual_secs = self.samples.iter().sum::<f64>() / self.samples.len() as f64;
+        if actual_secs <= 0.0 {
+            return self.difficulty;
+        }
+
+        let a = attempts(self.difficulty);
+        let scaled = (self.expected_secs / actual_secs)
+            .clamp(1.0 / self.max_adjustment, self.max_adjustment);
+        let a_new = a * scaled;
+        difficulty_from_attempts(a_new)
+    }
+}
+
+/// Expected number of hashes to satisfy a difficulty: `a = m / (m - d)`.
+fn attempts(difficulty: u128) -> f64 {
+    M / (M - difficulty as f64)
+}
+
+/// Inverse of [`attempts`]: `d = m - m / a`, clamped to a valid threshold.
+fn difficulty_from_attempts(attempts: f64) -> u128 {
+    if attempts <= 1.0 {
+        return 0;
+    }
+    let d = M - M / attempts;
+    if d >= M {
+        u128::max_value()
+    } else if d <= 0.0 {
+        0
+    } else {
+        d as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn difficulty(average: u128) -> u128 {
+        let m = u128::max_value();
+        m - m / average
+    }
+
+    #[test]
+    fn slow_solves_lower_difficulty() {
+        let mut r = Retarget::new(10.0, difficulty(100_000), 4, 4.0);
+        // Solves took twice as long as intended, so difficulty should drop.
+        r.record_solves([20.0, 20.0, 20.0, 20.0]);
+        assert!(r.next_difficulty() < r.difficulty());
+    }
+
+    #[test]
+    fn fast_solves_raise_difficulty() {
+        let mut r = Retarget::new(10.0, difficulty(100_000), 4, 4.0);
+        r.record_solves([5.0, 5.0, 5.0, 5.0]);
+        assert!(r.next_difficulty() > r.difficulty());
+    }
+
+    #[test]
+    fn adjustment_is_clamped() {
+        let mut r = Retarget::new(10.0, difficulty(100_000), 4, 4.0);
+        // A 100x slowdown is clamped to a 4x attempts reduction.
+        r.record_solve(1_000.0);
+        let expected = difficulty_from_attempts(attempts(r.difficulty()) / 4.0);
+        assert_eq!(r.next_difficulty(), expected);
+    }
+
+    #[test]
+    fn no_samples_keeps_difficulty() {
+        let r = Retarget::new(10.0, difficulty(100_000), 4, 4.0);
+        assert_eq!(r.next_difficulty(), r.difficulty());
+    }
+}